@@ -0,0 +1,402 @@
+//! An interval-keyed AVL tree answering overlap queries in O(k + log n).
+//!
+//! This is a separate subsystem from [`crate::avl_tree::AvlTree`]: keys here are
+//! `(low, high)` ranges rather than scalars, and each node additionally tracks
+//! `max_high`, the maximum `high` endpoint anywhere in its subtree. That lets
+//! [`IntervalTree::search_overlap`] prune a whole subtree whenever
+//! `node.left.max_high < query.low`, the standard augmented-interval-tree trick.
+//!
+//! The tree is ordered by `(low, high)`, so BST order and in-order traversal both
+//! follow `low` first.
+
+use std::collections::HashMap;
+
+use crate::rotation::{self, RotatableTree};
+
+pub type NodeId = u64;
+
+/// An inclusive `[low, high]` interval. Stored intervals are ordered by
+/// `(low, high)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Interval<K> {
+    pub low: K,
+    pub high: K,
+}
+
+impl<K: Ord + Copy> Interval<K> {
+    pub fn new(low: K, high: K) -> Self {
+        assert!(low <= high, "interval low must not exceed high");
+        Self { low, high }
+    }
+
+    fn overlaps(&self, other: &Interval<K>) -> bool {
+        self.low <= other.high && other.low <= self.high
+    }
+
+    fn contains_point(&self, point: K) -> bool {
+        self.low <= point && point <= self.high
+    }
+}
+
+struct Node<K, V> {
+    interval: Interval<K>,
+    value: V,
+    parent: Option<NodeId>,
+    left: Option<NodeId>,
+    right: Option<NodeId>,
+    height: i64,
+    max_high: K,
+}
+
+/// An AVL tree keyed by interval, supporting overlap queries.
+pub struct IntervalTree<K, V> {
+    nodes: HashMap<NodeId, Node<K, V>>,
+    root: Option<NodeId>,
+    next_id: NodeId,
+}
+
+impl<K, V> Default for IntervalTree<K, V>
+where
+    K: Ord + Copy,
+    V: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Copy, V: Clone> IntervalTree<K, V> {
+    pub fn new() -> Self {
+        Self {
+            nodes: HashMap::new(),
+            root: None,
+            next_id: 0,
+        }
+    }
+
+    fn node(&self, id: NodeId) -> &Node<K, V> {
+        self.nodes.get(&id).expect("dangling node id")
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> &mut Node<K, V> {
+        self.nodes.get_mut(&id).expect("dangling node id")
+    }
+
+    fn height_of(&self, id: Option<NodeId>) -> i64 {
+        id.map(|i| self.node(i).height).unwrap_or(0)
+    }
+
+    fn max_high_of(&self, id: Option<NodeId>) -> Option<K> {
+        id.map(|i| self.node(i).max_high)
+    }
+
+    fn alloc(&mut self, interval: Interval<K>, value: V, parent: Option<NodeId>) -> NodeId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.nodes.insert(
+            id,
+            Node {
+                interval,
+                value,
+                parent,
+                left: None,
+                right: None,
+                height: 1,
+                max_high: interval.high,
+            },
+        );
+        id
+    }
+
+    /// Recomputes `height` and `max_high` for `id` from its children, the same
+    /// bottom-up pass that fixes balance on insert/delete/rotation.
+    fn recompute(&mut self, id: NodeId) {
+        let (left, right, own_high) = {
+            let n = self.node(id);
+            (n.left, n.right, n.interval.high)
+        };
+        let left_h = self.height_of(left);
+        let right_h = self.height_of(right);
+        let mut max_high = own_high;
+        if let Some(h) = self.max_high_of(left) {
+            if h > max_high {
+                max_high = h;
+            }
+        }
+        if let Some(h) = self.max_high_of(right) {
+            if h > max_high {
+                max_high = h;
+            }
+        }
+        let n = self.node_mut(id);
+        n.height = 1 + left_h.max(right_h);
+        n.max_high = max_high;
+    }
+
+    fn balance_factor(&self, id: NodeId) -> i64 {
+        let n = self.node(id);
+        self.height_of(n.right) - self.height_of(n.left)
+    }
+
+    fn retrace(&mut self, id: Option<NodeId>) {
+        rotation::retrace(self, id)
+    }
+
+    pub fn insert(&mut self, low: K, high: K, value: V) {
+        let interval = Interval::new(low, high);
+        let Some(root) = self.root else {
+            let id = self.alloc(interval, value, None);
+            self.root = Some(id);
+            return;
+        };
+
+        let mut cur = root;
+        loop {
+            match interval.cmp(&self.node(cur).interval) {
+                std::cmp::Ordering::Equal => {
+                    self.node_mut(cur).value = value;
+                    self.retrace(Some(cur));
+                    return;
+                }
+                std::cmp::Ordering::Less => match self.node(cur).left {
+                    Some(next) => cur = next,
+                    None => {
+                        let id = self.alloc(interval, value, Some(cur));
+                        self.node_mut(cur).left = Some(id);
+                        break;
+                    }
+                },
+                std::cmp::Ordering::Greater => match self.node(cur).right {
+                    Some(next) => cur = next,
+                    None => {
+                        let id = self.alloc(interval, value, Some(cur));
+                        self.node_mut(cur).right = Some(id);
+                        break;
+                    }
+                },
+            }
+        }
+        self.retrace(Some(cur));
+    }
+
+    /// Returns every stored interval overlapping `[low, high]`, together with its
+    /// value, in O(k + log n) where k is the number of matches.
+    pub fn search_overlap(&self, low: K, high: K) -> Vec<(Interval<K>, V)> {
+        let query = Interval::new(low, high);
+        let mut out = Vec::new();
+        self.collect_overlap(self.root, &query, &mut out);
+        out
+    }
+
+    fn collect_overlap(&self, id: Option<NodeId>, query: &Interval<K>, out: &mut Vec<(Interval<K>, V)>) {
+        let Some(id) = id else { return };
+        let n = self.node(id);
+
+        if let Some(left_max) = self.max_high_of(n.left) {
+            if left_max >= query.low {
+                self.collect_overlap(n.left, query, out);
+            }
+        }
+        if n.interval.overlaps(query) {
+            out.push((n.interval, n.value.clone()));
+        }
+        if n.interval.low <= query.high {
+            self.collect_overlap(n.right, query, out);
+        }
+    }
+
+    /// Returns the first stored interval overlapping `[low, high]` found by
+    /// descending the tree, or `None` if there isn't one.
+    pub fn search_first_overlap(&self, low: K, high: K) -> Option<(Interval<K>, V)> {
+        let query = Interval::new(low, high);
+        let mut cur = self.root;
+        while let Some(id) = cur {
+            let n = self.node(id);
+            if n.interval.overlaps(&query) {
+                return Some((n.interval, n.value.clone()));
+            }
+            cur = match n.left.and_then(|l| self.max_high_of(Some(l))) {
+                Some(left_max) if left_max >= query.low => n.left,
+                _ => n.right,
+            };
+        }
+        None
+    }
+
+    /// Returns every stored interval containing `point`.
+    pub fn search_point(&self, point: K) -> Vec<(Interval<K>, V)> {
+        self.search_overlap(point, point)
+            .into_iter()
+            .filter(|(iv, _)| iv.contains_point(point))
+            .collect()
+    }
+
+    fn find(&self, interval: &Interval<K>) -> Option<NodeId> {
+        let mut cur = self.root;
+        while let Some(id) = cur {
+            cur = match interval.cmp(&self.node(id).interval) {
+                std::cmp::Ordering::Equal => return Some(id),
+                std::cmp::Ordering::Less => self.node(id).left,
+                std::cmp::Ordering::Greater => self.node(id).right,
+            };
+        }
+        None
+    }
+
+    fn min_node(&self, mut id: NodeId) -> NodeId {
+        while let Some(left) = self.node(id).left {
+            id = left;
+        }
+        id
+    }
+
+    fn reattach_to_parent(&mut self, parent: Option<NodeId>, old_child: NodeId, new_child: NodeId) {
+        rotation::reattach_to_parent(self, parent, old_child, new_child)
+    }
+
+    /// Splices `id` (which has at most one child) out of the tree, returning the
+    /// parent to retrace from.
+    fn remove_node_with_at_most_one_child(&mut self, id: NodeId) -> Option<NodeId> {
+        let parent = self.node(id).parent;
+        let child = self.node(id).left.or(self.node(id).right);
+
+        match child {
+            Some(c) => {
+                self.node_mut(c).parent = parent;
+                self.reattach_to_parent(parent, id, c);
+            }
+            None => match parent {
+                None => self.root = None,
+                Some(p) => {
+                    if self.node(p).left == Some(id) {
+                        self.node_mut(p).left = None;
+                    } else {
+                        self.node_mut(p).right = None;
+                    }
+                }
+            },
+        }
+        self.nodes.remove(&id);
+        parent
+    }
+
+    /// Removes the stored `[low, high]` interval exactly (matched by `(low, high)`
+    /// equality, not overlap), returning its value if it was present.
+    pub fn remove(&mut self, low: K, high: K) -> Option<V> {
+        let interval = Interval::new(low, high);
+        let id = self.find(&interval)?;
+
+        if self.node(id).left.is_some() && self.node(id).right.is_some() {
+            let successor = self.min_node(self.node(id).right.unwrap());
+            let succ_interval = self.node(successor).interval;
+            let succ_value = self.node(successor).value.clone();
+            let removed_value = std::mem::replace(&mut self.node_mut(id).value, succ_value);
+            self.node_mut(id).interval = succ_interval;
+            let retrace_from = self.remove_node_with_at_most_one_child(successor);
+            self.retrace(retrace_from);
+            return Some(removed_value);
+        }
+
+        let value = self.node(id).value.clone();
+        let retrace_from = self.remove_node_with_at_most_one_child(id);
+        self.retrace(retrace_from);
+        Some(value)
+    }
+}
+
+impl<K: Ord + Copy, V: Clone> RotatableTree for IntervalTree<K, V> {
+    fn root_mut(&mut self) -> &mut Option<NodeId> {
+        &mut self.root
+    }
+
+    fn parent_of(&self, id: NodeId) -> Option<NodeId> {
+        self.node(id).parent
+    }
+
+    fn left_of(&self, id: NodeId) -> Option<NodeId> {
+        self.node(id).left
+    }
+
+    fn right_of(&self, id: NodeId) -> Option<NodeId> {
+        self.node(id).right
+    }
+
+    fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) {
+        self.node_mut(id).parent = parent;
+    }
+
+    fn set_left(&mut self, id: NodeId, left: Option<NodeId>) {
+        self.node_mut(id).left = left;
+    }
+
+    fn set_right(&mut self, id: NodeId, right: Option<NodeId>) {
+        self.node_mut(id).right = right;
+    }
+
+    fn balance_factor_of(&self, id: NodeId) -> i64 {
+        self.balance_factor(id)
+    }
+
+    fn recompute(&mut self, id: NodeId) {
+        self.recompute(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_overlap_finds_all_intersecting_intervals() {
+        let mut tree = IntervalTree::new();
+        let intervals = [(1, 3), (5, 8), (2, 6), (15, 20), (10, 12)];
+        for (low, high) in intervals {
+            tree.insert(low, high, (low, high));
+        }
+
+        let mut found: Vec<_> = tree.search_overlap(4, 9).into_iter().map(|(iv, _)| (iv.low, iv.high)).collect();
+        found.sort();
+        assert_eq!(found, vec![(2, 6), (5, 8)]);
+    }
+
+    #[test]
+    fn search_first_overlap_matches_search_overlap_when_present() {
+        let mut tree = IntervalTree::new();
+        for (low, high) in [(1, 3), (5, 8), (2, 6), (15, 20)] {
+            tree.insert(low, high, ());
+        }
+        assert!(tree.search_first_overlap(4, 9).is_some());
+        assert!(tree.search_first_overlap(100, 200).is_none());
+    }
+
+    #[test]
+    fn search_point_only_returns_containing_intervals() {
+        let mut tree = IntervalTree::new();
+        for (low, high) in [(1, 3), (5, 8), (2, 6)] {
+            tree.insert(low, high, ());
+        }
+        let found: Vec<_> = tree.search_point(4).into_iter().map(|(iv, _)| (iv.low, iv.high)).collect();
+        assert_eq!(found, vec![(2, 6)]);
+    }
+
+    #[test]
+    fn remove_drops_the_exact_interval_and_leaves_others_searchable() {
+        let mut tree = IntervalTree::new();
+        let intervals = [(1, 3), (5, 8), (2, 6), (15, 20), (10, 12)];
+        for (low, high) in intervals {
+            tree.insert(low, high, (low, high));
+        }
+
+        assert_eq!(tree.remove(2, 6), Some((2, 6)));
+        assert_eq!(tree.remove(2, 6), None);
+
+        let mut found: Vec<_> = tree.search_overlap(4, 9).into_iter().map(|(iv, _)| (iv.low, iv.high)).collect();
+        found.sort();
+        assert_eq!(found, vec![(5, 8)]);
+
+        for (low, high) in [(1, 3), (5, 8), (15, 20), (10, 12)] {
+            assert_eq!(tree.remove(low, high), Some((low, high)));
+        }
+        assert!(tree.search_overlap(0, 100).is_empty());
+    }
+}
@@ -0,0 +1,21 @@
+//! A standalone, pointer-free AVL tree library.
+//!
+//! [`AvlTree`] is a plain, pointer-free key-value AVL tree: nodes live in an
+//! id-addressed arena so the structure can eventually sit behind any key-value
+//! backing store (e.g. a Scrypto `KeyValueStore`), which is why this crate takes
+//! its name from that eventual use case. As it stands, nothing here depends on
+//! Scrypto/sbor or touches a blueprint -- it's plain `std`, usable from any Rust
+//! program. Balance factors and subtree sizes are kept current on every mutation,
+//! which is what makes the order-statistic queries on [`AvlTree::select_by_index`]
+//! and [`AvlTree::rank`] O(log n).
+
+pub mod avl_tree;
+pub mod interval_tree;
+pub mod monoid;
+pub mod node;
+mod rotation;
+
+pub use avl_tree::{AvlTree, Cursor, IntegrityError};
+pub use interval_tree::IntervalTree;
+pub use monoid::{Aggregate, Max, Min, Monoid, Sum};
+pub use node::{Node, NodeId};
@@ -0,0 +1,130 @@
+//! The associative accumulator trait behind [`crate::avl_tree::AvlTree::range_aggregate`].
+//!
+//! Implementors must satisfy the monoid laws:
+//! - `combine(identity(), x) == combine(x, identity()) == x` for all `x`.
+//! - `combine(combine(a, b), c) == combine(a, combine(b, c))` for all `a, b, c`.
+//!
+//! Violating associativity silently corrupts aggregates after rotations, since the
+//! tree is free to recombine children in either grouping.
+
+/// An associative accumulator with an identity element.
+pub trait Monoid: Clone {
+    /// The identity element: `combine(identity(), x) == x`.
+    fn identity() -> Self;
+
+    /// Combines `self` with `other`. Must be associative.
+    fn combine(&self, other: &Self) -> Self;
+}
+
+/// The per-node accumulator an [`crate::avl_tree::AvlTree`] maintains bottom-up
+/// alongside `height`/`balance_factor`/`subtree_size`, keyed off of the tree's value
+/// type `V`.
+///
+/// `AvlTree<K, V>` defaults its aggregate slot to `()`, whose impl below is a no-op,
+/// so callers who only want order-statistics, a cursor, or `verify_integrity` never
+/// have to make `V` a [`Monoid`]. A tree opts into [`crate::avl_tree::AvlTree::range_aggregate`]
+/// by naming a third type parameter, e.g. `AvlTree<K, Sum, Sum>`, using the blanket
+/// impl below that lets any `Monoid` aggregate itself.
+pub trait Aggregate<V>: Clone {
+    /// The aggregate of an empty subtree.
+    fn identity() -> Self;
+
+    /// The aggregate contributed by a single node holding `value`.
+    fn from_value(value: &V) -> Self;
+
+    /// Combines two subtrees' aggregates into their parent's. Must be associative.
+    fn combine(&self, other: &Self) -> Self;
+}
+
+/// The default, no-op aggregate slot: every `AvlTree<K, V>` gets this unless it opts
+/// into a real one via a third type parameter.
+impl<V> Aggregate<V> for () {
+    fn identity() -> Self {}
+
+    fn from_value(_value: &V) -> Self {}
+
+    fn combine(&self, _other: &Self) -> Self {}
+}
+
+/// A [`Monoid`] can always aggregate itself: this is what makes `AvlTree<K, V, V>`
+/// work for any `V: Monoid`, matching the tree's pre-augmentation behavior of using
+/// the value type directly as its own accumulator.
+impl<M: Monoid> Aggregate<M> for M {
+    fn identity() -> Self {
+        Monoid::identity()
+    }
+
+    fn from_value(value: &M) -> Self {
+        value.clone()
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Monoid::combine(self, other)
+    }
+}
+
+/// The default integer-sum specialization: plain `i32`/`i64` values aggregate by
+/// summation, so they can be used as `V` in [`crate::avl_tree::AvlTree`] directly.
+impl Monoid for i32 {
+    fn identity() -> Self {
+        0
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        self + other
+    }
+}
+
+impl Monoid for i64 {
+    fn identity() -> Self {
+        0
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        self + other
+    }
+}
+
+/// Wraps a numeric value so it aggregates by summation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Sum(pub i64);
+
+impl Monoid for Sum {
+    fn identity() -> Self {
+        Sum(0)
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Sum(self.0 + other.0)
+    }
+}
+
+/// Wraps a numeric value so it aggregates by taking the minimum, with `i64::MAX`
+/// as identity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Min(pub i64);
+
+impl Monoid for Min {
+    fn identity() -> Self {
+        Min(i64::MAX)
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Min(self.0.min(other.0))
+    }
+}
+
+/// Wraps a numeric value so it aggregates by taking the maximum, with `i64::MIN`
+/// as identity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Max(pub i64);
+
+impl Monoid for Max {
+    fn identity() -> Self {
+        Max(i64::MIN)
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Max(self.0.max(other.0))
+    }
+}
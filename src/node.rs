@@ -0,0 +1,51 @@
+//! The node type backing [`crate::avl_tree::AvlTree`].
+//!
+//! Nodes are addressed by [`NodeId`] rather than by pointer or `Rc`/`RefCell` so the
+//! tree can eventually be backed by any key-value store (e.g. a Scrypto
+//! `KeyValueStore`) without interior mutability.
+
+use crate::monoid::Aggregate;
+
+/// Identifier of a node inside an [`crate::avl_tree::AvlTree`]'s backing store.
+pub type NodeId = u64;
+
+/// A single node of the tree.
+///
+/// `height` is the height of the subtree rooted at this node (a leaf has height 1),
+/// and `balance_factor` is `height(right) - height(left)`, always kept in `[-1, 1]`
+/// by the owning tree. `subtree_size` is the number of nodes in the subtree rooted
+/// here, i.e. `1 + size(left) + size(right)`. `A` is the tree's aggregate slot (see
+/// [`Aggregate`]); it defaults to `()` and costs nothing unless a tree opts into
+/// [`crate::avl_tree::AvlTree::range_aggregate`].
+#[derive(Debug, Clone)]
+pub struct Node<K, V, A = ()> {
+    pub key: K,
+    pub value: V,
+    pub parent: Option<NodeId>,
+    pub left: Option<NodeId>,
+    pub right: Option<NodeId>,
+    pub height: i64,
+    pub balance_factor: i64,
+    pub subtree_size: u64,
+    /// This node's value folded with both children's aggregates via
+    /// [`Aggregate::combine`]. Maintained by [`crate::avl_tree::AvlTree`] in the same
+    /// bottom-up pass that fixes `height`, `balance_factor` and `subtree_size`.
+    pub aggregate: A,
+}
+
+impl<K, V, A: Aggregate<V>> Node<K, V, A> {
+    pub fn new(key: K, value: V, parent: Option<NodeId>) -> Self {
+        let aggregate = A::from_value(&value);
+        Self {
+            key,
+            value,
+            parent,
+            left: None,
+            right: None,
+            height: 1,
+            balance_factor: 0,
+            subtree_size: 1,
+            aggregate,
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! The rotation/rebalance core shared by [`crate::avl_tree::AvlTree`] and
+//! [`crate::interval_tree::IntervalTree`].
+//!
+//! Both are arena-backed AVL trees that differ only in what they recompute
+//! bottom-up (subtree size + aggregate vs. `max_high`); the actual tree surgery —
+//! rotations, rebalancing, retracing to the root — lives here once via
+//! [`RotatableTree`] instead of being hand-copied per tree, so a rotation bugfix
+//! only has to be made in one place.
+
+use crate::node::NodeId;
+
+/// What [`rotate_left`]/[`rotate_right`]/[`rebalance`]/[`retrace`] need from an
+/// arena-backed AVL tree, without knowing what else it tracks per node.
+pub(crate) trait RotatableTree {
+    fn root_mut(&mut self) -> &mut Option<NodeId>;
+    fn parent_of(&self, id: NodeId) -> Option<NodeId>;
+    fn left_of(&self, id: NodeId) -> Option<NodeId>;
+    fn right_of(&self, id: NodeId) -> Option<NodeId>;
+    fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>);
+    fn set_left(&mut self, id: NodeId, left: Option<NodeId>);
+    fn set_right(&mut self, id: NodeId, right: Option<NodeId>);
+    /// `height(right) - height(left)` for `id`, as of the last [`RotatableTree::recompute`].
+    fn balance_factor_of(&self, id: NodeId) -> i64;
+    /// Recomputes whatever per-node bookkeeping (height, size, aggregate, max_high,
+    /// ...) `id` needs from its (already up to date) children.
+    fn recompute(&mut self, id: NodeId);
+}
+
+pub(crate) fn reattach_to_parent<T: RotatableTree>(
+    tree: &mut T,
+    parent: Option<NodeId>,
+    old_child: NodeId,
+    new_child: NodeId,
+) {
+    match parent {
+        None => *tree.root_mut() = Some(new_child),
+        Some(p) => {
+            if tree.left_of(p) == Some(old_child) {
+                tree.set_left(p, Some(new_child));
+            } else {
+                tree.set_right(p, Some(new_child));
+            }
+        }
+    }
+}
+
+pub(crate) fn rotate_left<T: RotatableTree>(tree: &mut T, id: NodeId) -> NodeId {
+    let parent = tree.parent_of(id);
+    let pivot = tree.right_of(id).expect("rotate_left needs a right child");
+    let pivot_left = tree.left_of(pivot);
+
+    tree.set_right(id, pivot_left);
+    if let Some(pl) = pivot_left {
+        tree.set_parent(pl, Some(id));
+    }
+    tree.set_left(pivot, Some(id));
+    tree.set_parent(id, Some(pivot));
+    tree.set_parent(pivot, parent);
+    reattach_to_parent(tree, parent, id, pivot);
+
+    tree.recompute(id);
+    tree.recompute(pivot);
+    pivot
+}
+
+pub(crate) fn rotate_right<T: RotatableTree>(tree: &mut T, id: NodeId) -> NodeId {
+    let parent = tree.parent_of(id);
+    let pivot = tree.left_of(id).expect("rotate_right needs a left child");
+    let pivot_right = tree.right_of(pivot);
+
+    tree.set_left(id, pivot_right);
+    if let Some(pr) = pivot_right {
+        tree.set_parent(pr, Some(id));
+    }
+    tree.set_right(pivot, Some(id));
+    tree.set_parent(id, Some(pivot));
+    tree.set_parent(pivot, parent);
+    reattach_to_parent(tree, parent, id, pivot);
+
+    tree.recompute(id);
+    tree.recompute(pivot);
+    pivot
+}
+
+/// Rebalances `id` if needed, returning the id of the node now standing where `id`
+/// used to be (itself, unless a rotation replaced it).
+pub(crate) fn rebalance<T: RotatableTree>(tree: &mut T, id: NodeId) -> NodeId {
+    tree.recompute(id);
+    let bf = tree.balance_factor_of(id);
+    if bf == 2 {
+        let right = tree.right_of(id).unwrap();
+        if tree.balance_factor_of(right) < 0 {
+            rotate_right(tree, right);
+        }
+        rotate_left(tree, id)
+    } else if bf == -2 {
+        let left = tree.left_of(id).unwrap();
+        if tree.balance_factor_of(left) > 0 {
+            rotate_left(tree, left);
+        }
+        rotate_right(tree, id)
+    } else {
+        id
+    }
+}
+
+/// Walks from `id` up to the root, rebalancing and recomputing bookkeeping on every
+/// ancestor.
+pub(crate) fn retrace<T: RotatableTree>(tree: &mut T, mut id: Option<NodeId>) {
+    while let Some(current) = id {
+        let balanced = rebalance(tree, current);
+        id = tree.parent_of(balanced);
+    }
+}